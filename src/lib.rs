@@ -8,7 +8,20 @@ If you are looking for a drop-dead simple way to write images to open in your im
 
 I designed this library to dump GPU textures for debugging purposes, but it is suitable for a wide variety of applications.
 
+# `no_std`
+
+The encoder is pure computation, so with `default-features = false` the crate builds
+`no_std` and only needs `alloc`.  The [`BGRA::encode_to`]/[`BGRA::encode_rle_to`] streaming
+helpers and the [`std::error::Error`] impl for [`DecodeError`] require the default `std`
+feature.
+
 */
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
 #[repr(C)]
 struct ImageSpecification {
     x_origin: u16,
@@ -35,32 +48,115 @@ struct Header {
 }
 //http://www.paulbourke.net/dataformats/tga/
 const IMAGE_ID: [u8; 17] = *b"drewcrawford/tgar";
+
+/**
+The pixel format of an image.
+
+Selects the `image_type`, `pixel_depth`, and color-map fields of the [`Header`].
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorType {
+    ///32-bit true color, one [`PixelBGRA`] per pixel.
+    BGRA32,
+    ///24-bit true color with no alpha, one [`PixelBGR`] per pixel.
+    BGR24,
+    ///8-bit single channel, one [`PixelGray`] per pixel.  Handy for depth/mask buffers.
+    Gray8,
+    ///8-bit indices into a `palette_len`-entry BGRA color map.
+    ColorMapped {
+        ///number of entries in the color map that follows the header.
+        palette_len: u16,
+    },
+}
+
 impl Header {
     ///Creates a header for a texture with the associated with/height in bgra format.
     fn new_bgra(width: u16, height: u16) -> Header {
+        Header::new(ColorType::BGRA32, width, height)
+    }
+    ///Creates a header for an image of the given color type and dimensions.
+    ///
+    /// Fills `pixel_depth`, `image_type`, and the color-map specification to match
+    /// `color_type`; always upper-left origin with no interleaving.
+    fn new(color_type: ColorType, width: u16, height: u16) -> Header {
+        //bits 3-0 of the descriptor are the attribute (alpha) bit count.
+        let (image_type, pixel_depth, attribute_bits, color_map_type, color_map_specification) = match color_type {
+            ColorType::BGRA32 => (2, 32, 8, 0, [0, 0, 0, 0, 0]),
+            ColorType::BGR24 => (2, 24, 0, 0, [0, 0, 0, 0, 0]),
+            ColorType::Gray8 => (3, 8, 0, 0, [0, 0, 0, 0, 0]), //uncompressed black-and-white
+            ColorType::ColorMapped { palette_len } => {
+                //first entry index 0, palette_len entries, 32-bit BGRA entries
+                let len = palette_len.to_le_bytes();
+                (1, 8, 0, 1, [0, 0, len[0], len[1], 32])
+            }
+        };
         Header {
             id_length:  IMAGE_ID.len() as u8,
-            color_map_type: 0, //no color map
-            image_type: 2, //uncompressed true-color image
-            color_map_specification: [0,0,0,0,0],
+            color_map_type,
+            image_type,
+            color_map_specification,
             image_specification: ImageSpecification {
                 x_origin: 0,
                 y_origin: 0,
                 image_width: width.to_le(),
                 image_height: height.to_le(),
-                pixel_depth: 32, //bpp, RGBA
-                image_descriptor: 0b1000 | //bits 3-0 are the "number of attribute bits associated with each pixel", for TGA 32
-                                            //this should be 8
+                pixel_depth,
+                image_descriptor: attribute_bits | //bits 3-0 are the "number of attribute bits associated with each pixel"
                                 //bit 4 must be 0
                                 //bit 5 - 0=>lower left origin, 1=> upper left origin
                                 0b1 << 5 |
                                 0, //bits 7-8 'interleaving' flag.  We specify we don't interleave, I guess.
             },
             image_id: IMAGE_ID.clone(),
-            //no color map data
+            //color map data (if any) to follow
             //image data to follow
         }
     }
+    ///Appends the 18-byte TGA header followed by the image ID to `out`, little-endian.
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[self.id_length, self.color_map_type, self.image_type]);
+        out.extend_from_slice(&self.color_map_specification);
+        let s = &self.image_specification;
+        out.extend_from_slice(&s.x_origin.to_le_bytes());
+        out.extend_from_slice(&s.y_origin.to_le_bytes());
+        out.extend_from_slice(&s.image_width.to_le_bytes());
+        out.extend_from_slice(&s.image_height.to_le_bytes());
+        out.extend_from_slice(&[s.pixel_depth, s.image_descriptor]);
+        out.extend_from_slice(&self.image_id);
+    }
+}
+
+///Run-length encodes one scanline's worth of pixels into `out`, never crossing the row.
+fn rle_encode_row(row: &[PixelBGRA], out: &mut Vec<u8>) {
+    let mut i = 0;
+    while i < row.len() {
+        //measure a run of identical pixels starting at i (capped at 128)
+        let mut run_len = 1;
+        while i + run_len < row.len() && run_len < 128 && row[i + run_len] == row[i] {
+            run_len += 1;
+        }
+        if run_len >= 2 {
+            out.push(0x80 | (run_len as u8 - 1));
+            let p = &row[i];
+            out.extend_from_slice(&[p.b, p.g, p.r, p.a]);
+            i += run_len;
+        } else {
+            //raw packet: literal pixels until a run begins or we hit 128
+            let start = i;
+            let mut raw_len = 0;
+            while i < row.len() && raw_len < 128 {
+                if i + 1 < row.len() && row[i] == row[i + 1] {
+                    break; //a run starts here, leave it for the next packet
+                }
+                raw_len += 1;
+                i += 1;
+            }
+            out.push(raw_len as u8 - 1);
+            for p in &row[start..start + raw_len] {
+                out.extend_from_slice(&[p.b, p.g, p.r, p.a]);
+            }
+        }
+    }
 }
 /**
 A BGRA pixel format.
@@ -79,6 +175,66 @@ pub struct PixelBGRA {
     pub a: u8,
 }
 
+/**
+A 24-bit BGR pixel, for [`ColorType::BGR24`] images with no alpha channel.
+
+# Memory layout
+
+Each b,g,r component is a u8, in that on-disk order.
+*/
+#[repr(C)]
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct PixelBGR {
+    pub b: u8,
+    pub g: u8,
+    pub r: u8,
+}
+
+/**
+A single-channel 8-bit pixel, for [`ColorType::Gray8`] images.
+*/
+#[repr(C)]
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash,Default)]
+pub struct PixelGray {
+    pub value: u8,
+}
+
+/**
+Borrowed 32-bit BGRA bitmap data with a possibly-padded row stride.
+
+GPU textures are frequently allocated with a row stride larger than `width * 4`; this
+trait lets the encoder read the valid pixels out of such a buffer without the caller
+pre-packing a tight slice first.  See [`BGRA::from_bitmap`] and [`BGRA::from_strided`].
+*/
+pub trait BitmapData {
+    ///The raw bytes, BGRA order, `height` rows of `stride` bytes each.
+    fn data(&self) -> &[u8];
+    ///Image width, in pixels.
+    fn width(&self) -> u16;
+    ///Image height, in pixels.
+    fn height(&self) -> u16;
+    ///Distance between the start of consecutive rows, in bytes.  At least `width * 4`.
+    fn stride(&self) -> usize;
+}
+
+/**
+The trivial [`BitmapData`] backing [`BGRA::from_strided`]: a borrowed byte slice plus its
+dimensions and row stride.
+*/
+pub struct StridedBitmap<'a> {
+    width: u16,
+    height: u16,
+    stride: usize,
+    data: &'a [u8],
+}
+
+impl<'a> BitmapData for StridedBitmap<'a> {
+    fn data(&self) -> &[u8] { self.data }
+    fn width(&self) -> u16 { self.width }
+    fn height(&self) -> u16 { self.height }
+    fn stride(&self) -> usize { self.stride }
+}
+
 /**
 An image encoded in TGA format.  32-bit, BGRA data.
 */
@@ -96,28 +252,374 @@ impl BGRA {
     ///
     /// The size of the data must match the provided width and height.
     pub fn new(width: u16, height: u16, data: &[PixelBGRA]) -> BGRA {
-        //header + data
-        let allocation_size = core::mem::size_of::<Header>() + core::mem::size_of::<PixelBGRA>() * data.len();
-        let data_offset = core::mem::size_of::<Header>() - 1; //because index 0 is written to, lol.  fucking oboes
         assert!(width as usize * height as usize == data.len());
-        let mut buf = Vec::with_capacity(allocation_size);
-        unsafe{
-            let header = Header::new_bgra(width, height);
-            let header_ptr = buf.as_mut_ptr() as *mut Header;
-            header_ptr.write(header);
-            //we promise to fill this size!
-            buf.set_len(allocation_size);
-            let mut body_ptr = &mut buf[data_offset] as *mut u8 as *mut PixelBGRA;
-            for pixel in data {
-                body_ptr.write(pixel.clone());
-                body_ptr = body_ptr.add(1);
-            }
-
-        };
+        let mut buf = Vec::with_capacity(core::mem::size_of::<Header>() + data.len() * core::mem::size_of::<PixelBGRA>());
+        Header::new_bgra(width, height).write_bytes(&mut buf);
+        for p in data {
+            buf.extend_from_slice(&[p.b, p.g, p.r, p.a]);
+        }
+        BGRA {
+            data: buf.into_boxed_slice()
+        }
+    }
+    ///Encodes a new image, run-length encoded (TGA image type 10).
+    ///
+    /// The size of the data must match the provided width and height.
+    ///
+    /// This is usually much smaller than [`BGRA::new`] for the large, mostly-flat
+    /// textures this crate is built to dump, while staying a trivial format.  Per the
+    /// TGA spec runs are not allowed to cross a scanline boundary, so packets are
+    /// flushed at the end of every row.
+    pub fn new_rle(width: u16, height: u16, data: &[PixelBGRA]) -> BGRA {
+        assert!(width as usize * height as usize == data.len());
+        let mut header = Header::new_bgra(width, height);
+        header.image_type = 10; //run-length encoded true-color image
+        let mut buf = Vec::new();
+        header.write_bytes(&mut buf);
+        for row in data.chunks(width as usize) {
+            rle_encode_row(row, &mut buf);
+        }
         BGRA {
             data: buf.into_boxed_slice()
         }
     }
+    ///Encodes a new 24-bit BGR image (no alpha).
+    ///
+    /// The size of the data must match the provided width and height.
+    pub fn new_bgr24(width: u16, height: u16, data: &[PixelBGR]) -> BGRA {
+        assert!(width as usize * height as usize == data.len());
+        let mut buf = Vec::with_capacity(core::mem::size_of::<Header>() + data.len() * 3);
+        Header::new(ColorType::BGR24, width, height).write_bytes(&mut buf);
+        for p in data {
+            buf.extend_from_slice(&[p.b, p.g, p.r]);
+        }
+        BGRA { data: buf.into_boxed_slice() }
+    }
+    ///Encodes a new 8-bit grayscale image, one channel per pixel.
+    ///
+    /// The size of the data must match the provided width and height.  Useful for dumping
+    /// single-channel GPU buffers such as depth or mask targets.
+    pub fn new_gray8(width: u16, height: u16, data: &[PixelGray]) -> BGRA {
+        assert!(width as usize * height as usize == data.len());
+        let mut buf = Vec::with_capacity(core::mem::size_of::<Header>() + data.len());
+        Header::new(ColorType::Gray8, width, height).write_bytes(&mut buf);
+        for p in data {
+            buf.push(p.value);
+        }
+        BGRA { data: buf.into_boxed_slice() }
+    }
+    ///Encodes a new color-mapped (indexed) image.
+    ///
+    /// `indices` are 8-bit lookups into `palette`, which is written after the header as a
+    /// BGRA color map.  The number of indices must match the provided width and height, and
+    /// the palette may have at most 256 entries.
+    pub fn new_indexed(width: u16, height: u16, palette: &[PixelBGRA], indices: &[u8]) -> BGRA {
+        assert!(width as usize * height as usize == indices.len());
+        assert!(palette.len() <= 256, "a color map holds at most 256 entries");
+        let mut buf = Vec::with_capacity(core::mem::size_of::<Header>() + palette.len() * 4 + indices.len());
+        Header::new(ColorType::ColorMapped { palette_len: palette.len() as u16 }, width, height).write_bytes(&mut buf);
+        for p in palette {
+            buf.extend_from_slice(&[p.b, p.g, p.r, p.a]);
+        }
+        buf.extend_from_slice(indices);
+        BGRA { data: buf.into_boxed_slice() }
+    }
+    ///Encodes a 32-bit BGRA image from a borrowed, possibly row-padded byte buffer.
+    ///
+    /// `stride_bytes` is the distance between the start of consecutive rows; only the first
+    /// `width * 4` bytes of each row are copied and the padding is skipped.  This saves
+    /// callers from pre-packing a tight `&[PixelBGRA]` slice out of a strided GPU texture.
+    pub fn from_strided(width: u16, height: u16, stride_bytes: usize, data: &[u8]) -> BGRA {
+        BGRA::from_bitmap(&StridedBitmap { width, height, stride: stride_bytes, data })
+    }
+    ///Encodes a 32-bit BGRA image from any [`BitmapData`], copying only the valid pixels
+    ///of each row and skipping the row padding.
+    pub fn from_bitmap<B: BitmapData>(bitmap: &B) -> BGRA {
+        let width = bitmap.width() as usize;
+        let height = bitmap.height() as usize;
+        let stride = bitmap.stride();
+        let row_bytes = width * core::mem::size_of::<PixelBGRA>();
+        assert!(stride >= row_bytes, "stride must be at least width * 4 bytes");
+        let data = bitmap.data();
+        let mut buf = Vec::with_capacity(core::mem::size_of::<Header>() + row_bytes * height);
+        Header::new_bgra(bitmap.width(), bitmap.height()).write_bytes(&mut buf);
+        for row in 0..height {
+            let start = row * stride;
+            buf.extend_from_slice(&data[start..start + row_bytes]);
+        }
+        BGRA { data: buf.into_boxed_slice() }
+    }
+    ///Streams an uncompressed image straight to a sink, without buffering the whole file.
+    ///
+    /// Writes the header, then the pixels in chunked [`std::io::Write::write_all`] calls as they
+    /// are pulled from `pixels`.  This lets callers write directly to a file or socket
+    /// instead of holding a second ~`width * height * 4` byte copy in memory, which is
+    /// the motivating case for the 5000×5000 textures this crate targets.
+    ///
+    /// Requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn encode_to<W: std::io::Write>(writer: &mut W, width: u16, height: u16, pixels: impl Iterator<Item = PixelBGRA>) -> std::io::Result<()> {
+        let mut head = Vec::with_capacity(core::mem::size_of::<Header>());
+        Header::new_bgra(width, height).write_bytes(&mut head);
+        writer.write_all(&head)?;
+        //batch pixels so we don't make a syscall per pixel
+        let mut chunk = Vec::with_capacity(4096);
+        for p in pixels {
+            chunk.extend_from_slice(&[p.b, p.g, p.r, p.a]);
+            if chunk.len() >= 4096 {
+                writer.write_all(&chunk)?;
+                chunk.clear();
+            }
+        }
+        if !chunk.is_empty() {
+            writer.write_all(&chunk)?;
+        }
+        Ok(())
+    }
+    ///Streams a run-length encoded image straight to a sink, a scanline at a time.
+    ///
+    /// Like [`BGRA::encode_to`] but emits TGA image type 10; runs are flushed at the end
+    /// of each row so they never cross a scanline boundary.  Only one row is buffered at a
+    /// time, so peak memory stays proportional to `width`, not the whole image.
+    ///
+    /// Requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn encode_rle_to<W: std::io::Write>(writer: &mut W, width: u16, height: u16, pixels: impl Iterator<Item = PixelBGRA>) -> std::io::Result<()> {
+        let mut header = Header::new_bgra(width, height);
+        header.image_type = 10; //run-length encoded true-color image
+        let mut head = Vec::with_capacity(core::mem::size_of::<Header>());
+        header.write_bytes(&mut head);
+        writer.write_all(&head)?;
+        let width = width as usize;
+        let mut row = Vec::with_capacity(width);
+        let mut packet = Vec::new();
+        for p in pixels {
+            row.push(p);
+            if row.len() == width {
+                packet.clear();
+                rle_encode_row(&row, &mut packet);
+                writer.write_all(&packet)?;
+                row.clear();
+            }
+        }
+        //flush any trailing partial row (shouldn't happen when the count matches)
+        if !row.is_empty() {
+            packet.clear();
+            rle_encode_row(&row, &mut packet);
+            writer.write_all(&packet)?;
+        }
+        Ok(())
+    }
+    ///Decodes a TGA image back into its pixels.
+    ///
+    /// Returns the `(width, height, pixels)` in a consistent top-down, row-major order.
+    /// Handles uncompressed (type 2) and run-length encoded (type 10) 32-bit true-color
+    /// data, skips the image-ID and color-map bytes, and flips lower-left-origin files so
+    /// the caller always gets the same orientation regardless of how the file was written.
+    ///
+    /// Only 32-bit BGRA is supported; other depths return [`DecodeError::UnsupportedDepth`].
+    pub fn decode(bytes: &[u8]) -> Result<(u16, u16, Vec<PixelBGRA>), DecodeError> {
+        //fixed part of the header is 18 bytes
+        if bytes.len() < 18 {
+            return Err(DecodeError::TooShort);
+        }
+        let read_u16 = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+        let id_length = bytes[0] as usize;
+        let image_type = bytes[2];
+        let color_map_length = read_u16(5) as usize;
+        let color_map_entry_size = bytes[7] as usize; //bits per entry
+        let width = read_u16(12);
+        let height = read_u16(14);
+        let pixel_depth = bytes[16];
+        let image_descriptor = bytes[17];
+        if pixel_depth != 32 {
+            return Err(DecodeError::UnsupportedDepth(pixel_depth));
+        }
+        //image-ID, then the color map, precede the image data
+        let color_map_bytes = color_map_length * ((color_map_entry_size + 7) / 8);
+        let mut offset = 18 + id_length + color_map_bytes;
+        let count = width as usize * height as usize;
+        let mut pixels = Vec::with_capacity(count);
+        let read_pixel = |at: usize| -> Result<PixelBGRA, DecodeError> {
+            if at + 4 > bytes.len() {
+                return Err(DecodeError::TooShort);
+            }
+            Ok(PixelBGRA {
+                b: bytes[at],
+                g: bytes[at + 1],
+                r: bytes[at + 2],
+                a: bytes[at + 3],
+            })
+        };
+        match image_type {
+            2 => {
+                for _ in 0..count {
+                    pixels.push(read_pixel(offset)?);
+                    offset += 4;
+                }
+            }
+            10 => {
+                while pixels.len() < count {
+                    if offset >= bytes.len() {
+                        return Err(DecodeError::TooShort);
+                    }
+                    let packet = bytes[offset];
+                    offset += 1;
+                    let run = (packet & 0x7f) as usize + 1;
+                    if packet & 0x80 != 0 {
+                        let pixel = read_pixel(offset)?;
+                        offset += 4;
+                        for _ in 0..run {
+                            pixels.push(pixel);
+                        }
+                    } else {
+                        for _ in 0..run {
+                            pixels.push(read_pixel(offset)?);
+                            offset += 4;
+                        }
+                    }
+                }
+                pixels.truncate(count);
+            }
+            other => return Err(DecodeError::UnsupportedImageType(other)),
+        }
+        //bit 5 of the image descriptor: 0 => lower-left origin, 1 => upper-left origin.
+        //we always hand back top-down rows, so flip lower-left files.
+        if image_descriptor & (1 << 5) == 0 && width != 0 {
+            let w = width as usize;
+            let rows = pixels.len() / w;
+            for row in 0..rows / 2 {
+                let (top, bottom) = (row * w, (rows - 1 - row) * w);
+                for col in 0..w {
+                    pixels.swap(top + col, bottom + col);
+                }
+            }
+        }
+        Ok((width, height, pixels))
+    }
+}
+
+/**
+An error produced while decoding a TGA image with [`BGRA::decode`].
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    ///The byte slice ended before a complete image could be read.
+    TooShort,
+    ///The pixel depth is not 32; only BGRA is supported.
+    UnsupportedDepth(u8),
+    ///The `image_type` is not an uncompressed (2) or RLE (10) true-color image.
+    UnsupportedImageType(u8),
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            DecodeError::TooShort => write!(f, "the data ended before a complete image"),
+            DecodeError::UnsupportedDepth(d) => write!(f, "unsupported pixel depth {} (only 32-bit BGRA is supported)", d),
+            DecodeError::UnsupportedImageType(t) => write!(f, "unsupported image type {} (only 2 and 10 are supported)", t),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+/**
+Which corner the first pixel row maps to, i.e. the `image_descriptor` origin bit.
+
+Pick [`Origin::LowerLeft`] to work in the OpenGL / math convention (y grows upward) or
+[`Origin::UpperLeft`] for the usual screen convention (y grows downward), and let the
+TGA origin bit record the choice instead of inverting your y-loops by hand.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Origin {
+    ///First row is the top of the image; y grows downward (screen convention).
+    UpperLeft,
+    ///First row is the bottom of the image; y grows upward (OpenGL / math convention).
+    LowerLeft,
+}
+
+/**
+A mutable BGRA drawing surface for procedural image generation.
+
+Owns a `width * height` buffer of [`PixelBGRA`] which you paint with [`Canvas::set_pixel`]
+and friends, then finalize into a [`BGRA`] with [`Canvas::into_tga`].  The [`Origin`]
+controls the orientation recorded in the file; the in-memory buffer is always row 0 first.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Canvas {
+    width: u16,
+    height: u16,
+    origin: Origin,
+    pixels: Vec<PixelBGRA>,
+}
+
+impl Canvas {
+    ///Creates an upper-left-origin canvas, cleared to the default (transparent) pixel.
+    pub fn new(width: u16, height: u16) -> Canvas {
+        Canvas::with_origin(width, height, Origin::UpperLeft)
+    }
+    ///Creates a canvas with the given [`Origin`], cleared to the default pixel.
+    pub fn with_origin(width: u16, height: u16, origin: Origin) -> Canvas {
+        Canvas {
+            width,
+            height,
+            origin,
+            pixels: alloc::vec![PixelBGRA::default(); width as usize * height as usize],
+        }
+    }
+    ///The width of the canvas, in pixels.
+    pub fn width(&self) -> u16 { self.width }
+    ///The height of the canvas, in pixels.
+    pub fn height(&self) -> u16 { self.height }
+    ///Sets the origin recorded when the canvas is finalized.
+    pub fn set_origin(&mut self, origin: Origin) {
+        self.origin = origin;
+    }
+    fn index(&self, x: u16, y: u16) -> usize {
+        assert!(x < self.width && y < self.height, "pixel ({}, {}) out of bounds", x, y);
+        y as usize * self.width as usize + x as usize
+    }
+    ///Sets the pixel at `(x, y)`.  Panics if out of bounds.
+    pub fn set_pixel(&mut self, x: u16, y: u16, pixel: PixelBGRA) {
+        let i = self.index(x, y);
+        self.pixels[i] = pixel;
+    }
+    ///Returns the pixel at `(x, y)`.  Panics if out of bounds.
+    pub fn get_pixel(&self, x: u16, y: u16) -> PixelBGRA {
+        self.pixels[self.index(x, y)]
+    }
+    ///Fills the entire canvas with `pixel`.
+    pub fn fill(&mut self, pixel: PixelBGRA) {
+        for p in &mut self.pixels {
+            *p = pixel;
+        }
+    }
+    ///Flips the canvas top-to-bottom in place.
+    pub fn flip_vertical(&mut self) {
+        let w = self.width as usize;
+        let rows = self.height as usize;
+        for row in 0..rows / 2 {
+            let (top, bottom) = (row * w, (rows - 1 - row) * w);
+            for col in 0..w {
+                self.pixels.swap(top + col, bottom + col);
+            }
+        }
+    }
+    ///Finalizes the canvas into an encoded [`BGRA`] image, honoring the [`Origin`].
+    pub fn into_tga(self) -> BGRA {
+        let mut header = Header::new_bgra(self.width, self.height);
+        if let Origin::LowerLeft = self.origin {
+            header.image_specification.image_descriptor &= !(1 << 5); //clear => lower-left origin
+        }
+        let mut buf = Vec::with_capacity(core::mem::size_of::<Header>() + self.pixels.len() * core::mem::size_of::<PixelBGRA>());
+        header.write_bytes(&mut buf);
+        for p in &self.pixels {
+            buf.extend_from_slice(&[p.b, p.g, p.r, p.a]);
+        }
+        BGRA { data: buf.into_boxed_slice() }
+    }
 }
 //boilerplate
 impl Default for PixelBGRA {
@@ -181,7 +683,8 @@ impl AsMut<[u8]> for BGRA {
 
 
 ///Unit tests.
-#[cfg(test)]
+//these exercise the file-writing path, so they need `std`.
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use std::fs::File;
     use std::io::Write;
@@ -222,6 +725,124 @@ mod tests {
         file.write(&data).unwrap();
     }
     #[test]
+    fn rle() {
+        let mut pixels = Vec::new();
+        let width = 240;
+        let height = 240;
+        for x in 0..width {
+            for _y in 0..height {
+                //big flat runs per row, which RLE should collapse nicely
+                pixels.push(PixelBGRA {
+                    r: x,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                })
+            }
+        }
+        let rle = BGRA::new_rle(width as u16, height as u16, &pixels);
+        let data = rle.into_data();
+        let mut file = File::create(Path::new("rle.tga")).unwrap();
+        file.write(&data).unwrap();
+    }
+    #[test]
+    fn round_trip() {
+        let mut pixels = Vec::new();
+        let width = 64;
+        let height = 48;
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(PixelBGRA {
+                    r: x as u8,
+                    g: y as u8,
+                    b: 17,
+                    a: 255,
+                })
+            }
+        }
+        //both encoders must decode back to the same top-down pixels
+        let plain = BGRA::new(width, height, &pixels);
+        let (w, h, got) = BGRA::decode(plain.as_ref()).unwrap();
+        assert_eq!((w, h), (width, height));
+        assert_eq!(got, pixels);
+
+        let rle = BGRA::new_rle(width, height, &pixels);
+        let (w, h, got) = BGRA::decode(rle.as_ref()).unwrap();
+        assert_eq!((w, h), (width, height));
+        assert_eq!(got, pixels);
+    }
+    #[test]
+    fn streaming_matches_new() {
+        let mut pixels = Vec::new();
+        let width = 32;
+        let height = 20;
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(PixelBGRA { r: x as u8, g: y as u8, b: 9, a: 255 })
+            }
+        }
+        //the streaming encoder must produce byte-identical output to the buffered one
+        let mut streamed = Vec::new();
+        BGRA::encode_to(&mut streamed, width, height, pixels.iter().copied()).unwrap();
+        assert_eq!(streamed.as_slice(), BGRA::new(width, height, &pixels).as_ref());
+
+        let mut streamed_rle = Vec::new();
+        BGRA::encode_rle_to(&mut streamed_rle, width, height, pixels.iter().copied()).unwrap();
+        assert_eq!(streamed_rle.as_slice(), BGRA::new_rle(width, height, &pixels).as_ref());
+    }
+    #[test]
+    fn color_types() {
+        use crate::{PixelBGR, PixelGray};
+        let gray: Vec<_> = (0..16u16 * 16).map(|i| PixelGray { value: i as u8 }).collect();
+        let g = BGRA::new_gray8(16, 16, &gray);
+        File::create(Path::new("gray.tga")).unwrap().write(g.as_ref()).unwrap();
+
+        let bgr: Vec<_> = (0..16u16 * 16).map(|i| PixelBGR { b: i as u8, g: 0, r: 255 }).collect();
+        let b = BGRA::new_bgr24(16, 16, &bgr);
+        File::create(Path::new("bgr24.tga")).unwrap().write(b.as_ref()).unwrap();
+
+        let palette = [PixelBGRA::default(), PixelBGRA { r: 255, g: 0, b: 0, a: 255 }];
+        let indices: Vec<u8> = (0..16u16 * 16).map(|i| (i % 2) as u8).collect();
+        let idx = BGRA::new_indexed(16, 16, &palette, &indices);
+        File::create(Path::new("indexed.tga")).unwrap().write(idx.as_ref()).unwrap();
+    }
+    #[test]
+    fn canvas() {
+        use crate::{Canvas, Origin};
+        let mut canvas = Canvas::with_origin(8, 8, Origin::LowerLeft);
+        canvas.fill(PixelBGRA { r: 0, g: 0, b: 0, a: 255 });
+        canvas.set_pixel(2, 3, PixelBGRA { r: 255, g: 0, b: 0, a: 255 });
+        assert_eq!(canvas.get_pixel(2, 3), PixelBGRA { r: 255, g: 0, b: 0, a: 255 });
+        canvas.flip_vertical();
+        assert_eq!(canvas.get_pixel(2, 4), PixelBGRA { r: 255, g: 0, b: 0, a: 255 });
+        let tga = canvas.into_tga();
+        File::create(Path::new("canvas.tga")).unwrap().write(tga.as_ref()).unwrap();
+    }
+    #[test]
+    fn strided() {
+        //a 2x2 image in a buffer whose rows are padded to 12 bytes (one extra pixel)
+        let width = 2u16;
+        let height = 2u16;
+        let stride = 12usize;
+        let pixels = [
+            PixelBGRA { b: 1, g: 2, r: 3, a: 255 },
+            PixelBGRA { b: 4, g: 5, r: 6, a: 255 },
+            PixelBGRA { b: 7, g: 8, r: 9, a: 255 },
+            PixelBGRA { b: 10, g: 11, r: 12, a: 255 },
+        ];
+        let mut raw = Vec::new();
+        for row in 0..height as usize {
+            for col in 0..width as usize {
+                let p = pixels[row * width as usize + col];
+                raw.extend_from_slice(&[p.b, p.g, p.r, p.a]);
+            }
+            raw.extend_from_slice(&[0, 0, 0, 0]); //padding to reach the stride
+        }
+        let strided = BGRA::from_strided(width, height, stride, &raw);
+        //the padding must not leak into the tightly-packed output
+        assert_eq!(strided, BGRA::new(width, height, &pixels));
+    }
+    #[test]
     fn big() {
         let mut pixels = Vec::new();
         let width = 5000;